@@ -0,0 +1,133 @@
+use crate::{
+    moves::{MoveUnpkd, convert_moves_to_string},
+    state::{CubeState, metrics::SolvedPieceCounts},
+};
+
+/// The states a solution goes through: the scrambled state, then the state after each move.
+pub fn solution_states<S: CubeState>(scramble: &[MoveUnpkd], solution: &[MoveUnpkd]) -> Vec<S> {
+    let mut state = S::default();
+    for m in scramble {
+        state.make_move(*m);
+    }
+
+    let mut states = Vec::with_capacity(solution.len() + 1);
+    states.push(state.clone());
+    for m in solution {
+        state.make_move(*m);
+        states.push(state.clone());
+    }
+    states
+}
+
+/// The solved piece counts of each of `states`, ignoring whole-cube rotations
+/// (see `CubeState::solved_piece_counts_up_to_rotation`).
+pub fn solved_growth<S: CubeState>(states: &[S]) -> Vec<SolvedPieceCounts> {
+    states
+        .iter()
+        .map(|s| s.solved_piece_counts_up_to_rotation())
+        .collect()
+}
+
+fn counts_to_json(counts: &SolvedPieceCounts, value: impl Fn(usize, usize) -> usize) -> String {
+    let fields: Vec<String> = counts
+        .0
+        .iter()
+        .map(|c| format!("\"{}\": {}", c.kind.name(), value(c.solved, c.total)))
+        .collect();
+    format!("{{{}}}", fields.join(", "))
+}
+
+/// Formats the `solved_growth` of the `solution_states` as JSON, with one entry per step
+/// (step 0 being the scrambled state, step `i` the state after the `i`th move):
+///
+/// ```json
+/// {
+///   "scramble": "...",
+///   "solution": "...",
+///   "totals": {"corners": 8, ...},
+///   "steps": [
+///     {"step": 0, "move": null, "solved": {"corners": 1, ...}, "solved_total": 9},
+///     {"step": 1, "move": "Rw", "solved": {"corners": 1, ...}, "solved_total": 10},
+///     ...
+///   ]
+/// }
+/// ```
+pub fn solved_growth_to_json(
+    scramble: &[MoveUnpkd],
+    solution: &[MoveUnpkd],
+    growth: &[SolvedPieceCounts],
+) -> String {
+    assert_eq!(growth.len(), solution.len() + 1);
+
+    let steps: Vec<String> = growth
+        .iter()
+        .enumerate()
+        .map(|(step, counts)| {
+            let move_ = match step {
+                0 => "null".to_string(),
+                _ => format!("\"{}\"", solution[step - 1]),
+            };
+            format!(
+                "    {{\"step\": {step}, \"move\": {move_}, \"solved\": {}, \"solved_total\": {}}}",
+                counts_to_json(counts, |solved, _| solved),
+                counts.solved()
+            )
+        })
+        .collect();
+
+    format!(
+        "{{\n  \"scramble\": \"{}\",\n  \"solution\": \"{}\",\n  \"totals\": {},\n  \"steps\": [\n{}\n  ]\n}}",
+        convert_moves_to_string(scramble),
+        convert_moves_to_string(solution),
+        counts_to_json(&growth[0], |_, total| total),
+        steps.join(",\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::moves::convert_string_to_moves;
+    use crate::state::{State, state_4x4::State4x4};
+
+    #[test]
+    fn solution_states_end_solved_after_inverse() {
+        let scramble = convert_string_to_moves("R U Fw2 Lw' D");
+        let solution = convert_string_to_moves("D' Lw Fw2 U' R'");
+
+        let states = solution_states::<State>(&scramble, &solution);
+        assert_eq!(states.len(), solution.len() + 1);
+        assert_ne!(states[0], State::new());
+        assert_eq!(states[solution.len()], State::new());
+
+        let growth = solved_growth(&states);
+        assert_eq!(growth.len(), states.len());
+        assert_eq!(growth[solution.len()].solved(), 92);
+    }
+
+    #[test]
+    fn rotated_4x4_solution_ends_fully_solved() {
+        let solution = convert_string_to_moves("Rw Lw'");
+        let growth = solved_growth(&solution_states::<State4x4>(&[], &solution));
+        let last = growth.last().unwrap();
+        assert_eq!((last.solved(), last.total()), (56, 56));
+    }
+
+    #[test]
+    fn json_has_documented_shape() {
+        let scramble = convert_string_to_moves("R");
+        let solution = convert_string_to_moves("R'");
+        let growth = solved_growth(&solution_states::<State>(&scramble, &solution));
+        let json = solved_growth_to_json(&scramble, &solution, &growth);
+
+        assert!(json.starts_with("{\n  \"scramble\": \"R\",\n  \"solution\": \"R'\",\n"));
+        assert!(json.contains(
+            "\"totals\": {\"corners\": 8, \"midges\": 12, \"wings\": 24, \"x_centers\": 24, \"plus_centers\": 24}"
+        ));
+        assert!(json.contains("{\"step\": 0, \"move\": null, \"solved\": {"));
+        assert!(json.contains(
+            "{\"step\": 1, \"move\": \"R'\", \"solved\": {\"corners\": 8, \"midges\": 12, \"wings\": 24, \"x_centers\": 24, \"plus_centers\": 24}, \"solved_total\": 92}"
+        ));
+        assert!(json.ends_with("\n  ]\n}"));
+    }
+}
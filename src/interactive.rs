@@ -0,0 +1,236 @@
+use std::io::{BufRead, Write};
+
+use crate::{
+    moves::{MovePkd, MoveUnpkd, convert_moves_to_string, try_convert_string_to_moves},
+    state::{CubeState, state_to_img::export_state_to_image, state_to_text::state_to_ansi_string},
+};
+
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+const HELP: &str = "\
+<moves>        apply moves, e.g. Rw U2 F'
+undo [n]       undo the last n moves (default 1)
+redo [n]       redo the last n undone moves (default 1)
+reset          go back to the solved state
+img [path]     save the current state as an image (default out.png)
+help           show this message
+quit           exit";
+
+/// The moves applied so far, plus the moves that were undone and can still be redone.
+///
+/// The state is always the solved state with `applied` made on it.
+#[derive(Default)]
+struct Session<S: CubeState> {
+    state: S,
+    applied: Vec<MovePkd>,
+    undone: Vec<MovePkd>,
+}
+
+impl<S: CubeState> Session<S> {
+    fn apply(&mut self, moves: Vec<MoveUnpkd>) {
+        for m in moves {
+            self.state.make_move(m);
+            self.applied.push(MovePkd::from(m));
+        }
+        self.undone.clear();
+    }
+
+    fn undo(&mut self, n: usize) -> usize {
+        let n = n.min(self.applied.len());
+        for _ in 0..n {
+            let m = self.applied.pop().unwrap();
+            self.undone.push(m);
+        }
+        self.replay();
+        n
+    }
+
+    fn redo(&mut self, n: usize) -> usize {
+        let n = n.min(self.undone.len());
+        for _ in 0..n {
+            let m = self.undone.pop().unwrap();
+            self.state.make_move(m);
+            self.applied.push(m);
+        }
+        n
+    }
+
+    fn reset(&mut self) {
+        *self = Session::default();
+    }
+
+    fn replay(&mut self) {
+        self.state = S::default();
+        for m in &self.applied {
+            self.state.make_move(*m);
+        }
+    }
+
+    fn applied_moves_string(&self) -> String {
+        let moves: Vec<MoveUnpkd> = self.applied.iter().map(|m| MoveUnpkd::from(*m)).collect();
+        convert_moves_to_string(&moves)
+    }
+}
+
+fn parse_count(arg: Option<&str>) -> Result<usize, String> {
+    match arg {
+        None => Ok(1),
+        Some(arg) => arg
+            .parse()
+            .map_err(|_| format!("not a number of moves: {arg:?}")),
+    }
+}
+
+/// Handles one line of input, returning the message to show under the net,
+/// or `None` if the user asked to quit.
+fn handle_line<S: CubeState>(session: &mut Session<S>, line: &str) -> Option<String> {
+    let mut words = line.split_whitespace();
+    let command = words.next().unwrap_or("");
+    let arg = words.next();
+
+    let message = match command {
+        "" => String::new(),
+        "quit" | "exit" => return None,
+        "help" => HELP.to_string(),
+        "reset" => {
+            session.reset();
+            "reset to solved".to_string()
+        }
+        "undo" => match parse_count(arg) {
+            Ok(n) => format!("undid {} move(s)", session.undo(n)),
+            Err(e) => e,
+        },
+        "redo" => match parse_count(arg) {
+            Ok(n) => format!("redid {} move(s)", session.redo(n)),
+            Err(e) => e,
+        },
+        "img" => {
+            let path = arg.unwrap_or("out.png");
+            match export_state_to_image(&session.state, path) {
+                Ok(()) => format!("saved image to {path}"),
+                Err(e) => format!("failed to save image to {path}: {e}"),
+            }
+        }
+        _ => match try_convert_string_to_moves(line) {
+            Ok(moves) => {
+                session.apply(moves);
+                String::new()
+            }
+            Err(e) => format!("{e} (type `help` for commands)"),
+        },
+    };
+    Some(message)
+}
+
+fn draw<S: CubeState>(session: &Session<S>, message: &str) -> std::io::Result<()> {
+    print!("{CLEAR_SCREEN}");
+    println!("{}", state_to_ansi_string(&session.state));
+    println!();
+    println!("moves: {}", session.applied_moves_string());
+    if !message.is_empty() {
+        println!("{message}");
+    }
+    print!("> ");
+    std::io::stdout().flush()
+}
+
+/// Runs an interactive session on stdin/stdout until `quit` or end of input.
+pub fn run<S: CubeState>() {
+    let mut session = Session::<S>::default();
+    let mut message = "type moves to apply them, or `help` for commands".to_string();
+    let stdin = std::io::stdin();
+
+    loop {
+        if let Err(e) = draw(&session, &message) {
+            eprintln!("failed to write to stdout: {e}");
+            return;
+        }
+
+        let mut line = String::new();
+        match stdin.lock().read_line(&mut line) {
+            Ok(0) => {
+                println!();
+                return;
+            }
+            Ok(_) => {}
+            // the invalid line has been consumed, so the session can carry on
+            Err(e) if e.kind() == std::io::ErrorKind::InvalidData => {
+                message = format!("could not read input: {e}");
+                continue;
+            }
+            Err(e) => {
+                eprintln!("failed to read from stdin: {e}");
+                return;
+            }
+        }
+
+        match handle_line(&mut session, line.trim()) {
+            Some(m) => message = m,
+            None => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::State;
+
+    fn session_after(moves: &str) -> Session<State> {
+        let mut session = Session::default();
+        session.apply(try_convert_string_to_moves(moves).unwrap());
+        session
+    }
+
+    #[test]
+    fn undo_and_redo_restore_the_same_states() {
+        let mut session = session_after("R U Fw'");
+        let after_all = session.state.clone();
+
+        assert_eq!(session.undo(2), 2);
+        assert_eq!(session.state, session_after("R").state);
+        assert_eq!(session.applied_moves_string(), "R");
+
+        assert_eq!(session.redo(1), 1);
+        assert_eq!(session.state, session_after("R U").state);
+
+        assert_eq!(session.redo(1), 1);
+        assert_eq!(session.state, after_all);
+        assert_eq!(session.applied_moves_string(), "R U Fw'");
+    }
+
+    #[test]
+    fn undo_and_redo_clamp_to_the_stack_size() {
+        let mut session = session_after("R U");
+
+        assert_eq!(session.undo(5), 2);
+        assert_eq!(session.state, State::new());
+        assert_eq!(session.undo(1), 0);
+
+        assert_eq!(session.redo(5), 2);
+        assert_eq!(session.state, session_after("R U").state);
+        assert_eq!(session.redo(1), 0);
+    }
+
+    #[test]
+    fn apply_clears_redo() {
+        let mut session = session_after("R U");
+        session.undo(1);
+        session.apply(try_convert_string_to_moves("F").unwrap());
+
+        assert_eq!(session.redo(1), 0);
+        assert_eq!(session.applied_moves_string(), "R F");
+        assert_eq!(session.state, session_after("R F").state);
+    }
+
+    #[test]
+    fn reset_clears_everything() {
+        let mut session = session_after("R U");
+        session.undo(1);
+        session.reset();
+
+        assert_eq!(session.state, State::new());
+        assert_eq!(session.undo(1), 0);
+        assert_eq!(session.redo(1), 0);
+    }
+}
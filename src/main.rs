@@ -1,27 +1,119 @@
+pub mod growth;
+pub mod interactive;
 pub mod moves;
+pub mod scramble;
 pub mod state;
 pub mod utils;
 
-use state::State;
-use state::state_to_img::export_state_to_image;
 use std::io::BufRead;
 
-use crate::{moves::convert_string_to_moves, state::MoveableState};
+use moves::{MoveUnpkd, try_convert_string_to_moves};
+use scramble::{ScrambleRequirements, validate_scramble};
+use state::{CubeState, State, state_4x4::State4x4, state_to_img::export_states_strip_to_image};
 
-fn main() {
-    // test: Fw' R' B Dw Uw Rw Lw' R D2 B2 R' D2 R' Fw2 R' Fw Bw2 Uw' Rw D' L' Bw2 F2 Uw2 Bw' Uw' Rw2 L2 Fw' F' R' Fw' R' Fw' B L2 Bw L2 Bw2 F' R2 D Lw2 L Rw2 R Fw Uw2 Lw D Dw L2 Fw' D Fw2 Lw' Bw' D' Bw2 Rw'
-    loop {
-        let mut line = String::new();
-        let stdin = std::io::stdin();
-        stdin.lock().read_line(&mut line).unwrap();
+const USAGE: &str = "\
+usage: solver_5x5 [--4x4] [COMMAND]
+
+commands:
+  (none)                                   interactive mode
+  validate [SCRAMBLE...]                   check scrambles (read from stdin if none are given)
+  growth SCRAMBLE SOLUTION [--strip PATH]  print solved piece counts after each move as JSON,
+                                           optionally saving renders of every step to PATH";
+
+/// Validates each scramble (one per line) against the official requirements for `S`,
+/// printing any violations. Returns whether all scrambles were valid.
+fn validate_scrambles<S, I>(scrambles: I) -> bool
+where
+    S: CubeState,
+    I: IntoIterator<Item = String>,
+{
+    let requirements = ScrambleRequirements::wca::<S>().unwrap();
+    let mut all_valid = true;
+
+    for (n, scramble) in scrambles.into_iter().enumerate() {
+        let violations = validate_scramble::<S>(&scramble, &requirements);
+        if violations.is_empty() {
+            println!("scramble {}: ok", n + 1);
+        } else {
+            all_valid = false;
+            println!("scramble {}: {} violation(s)", n + 1, violations.len());
+            for v in violations {
+                println!("  {v}");
+            }
+        }
+    }
+
+    all_valid
+}
 
-        let line = line.strip_suffix("\n").unwrap();
-        let moves = convert_string_to_moves(line);
+fn parse_moves_or_exit(s: &str) -> Vec<MoveUnpkd> {
+    try_convert_string_to_moves(s).unwrap_or_else(|e| {
+        eprintln!("{e}");
+        std::process::exit(2);
+    })
+}
 
-        let mut s = State::new();
-        for m in moves {
-            s.make_move(m);
+fn print_solved_growth<S: CubeState>(args: &[String]) {
+    let (scramble, solution, strip_path) = match args {
+        [scramble, solution] => (scramble, solution, None),
+        [scramble, solution, flag, path] if flag == "--strip" => (scramble, solution, Some(path)),
+        _ => {
+            eprintln!("{USAGE}");
+            std::process::exit(2);
         }
-        export_state_to_image(&s, "out.png");
+    };
+    let scramble = parse_moves_or_exit(scramble);
+    let solution = parse_moves_or_exit(solution);
+
+    let states = growth::solution_states::<S>(&scramble, &solution);
+    let growth = growth::solved_growth(&states);
+    println!(
+        "{}",
+        growth::solved_growth_to_json(&scramble, &solution, &growth)
+    );
+
+    if let Some(path) = strip_path
+        && let Err(e) = export_states_strip_to_image(&states, 10, path)
+    {
+        eprintln!("failed to save strip to {path}: {e}");
+        std::process::exit(2);
+    }
+}
+
+fn run_command<S: CubeState>(args: &[String]) {
+    match args.first().map(String::as_str) {
+        None => interactive::run::<S>(),
+        Some("growth") => print_solved_growth::<S>(&args[1..]),
+        Some("validate") => {
+            // scrambles are either given as arguments or read from stdin, one per line
+            let all_valid = if args.len() > 1 {
+                validate_scrambles::<S, _>(args[1..].iter().cloned())
+            } else {
+                let lines: Result<Vec<String>, _> = std::io::stdin().lock().lines().collect();
+                let lines = lines.unwrap_or_else(|e| {
+                    eprintln!("failed to read scrambles from stdin: {e}");
+                    std::process::exit(2);
+                });
+                validate_scrambles::<S, _>(lines.into_iter().filter(|l| !l.trim().is_empty()))
+            };
+            if !all_valid {
+                std::process::exit(1);
+            }
+        }
+        Some(other) => {
+            eprintln!("unknown command {other:?}");
+            eprintln!("{USAGE}");
+            std::process::exit(2);
+        }
+    }
+}
+
+fn main() {
+    // test: Fw' R' B Dw Uw Rw Lw' R D2 B2 R' D2 R' Fw2 R' Fw Bw2 Uw' Rw D' L' Bw2 F2 Uw2 Bw' Uw' Rw2 L2 Fw' F' R' Fw' R' Fw' B L2 Bw L2 Bw2 F' R2 D Lw2 L Rw2 R Fw Uw2 Lw D Dw L2 Fw' D Fw2 Lw' Bw' D' Bw2 Rw'
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match args.first().map(String::as_str) {
+        Some("--4x4") => run_command::<State4x4>(&args[1..]),
+        _ => run_command::<State>(&args),
     }
 }
@@ -1,9 +1,11 @@
+use std::str::FromStr;
+
 use itertools::Itertools;
 use num_enum::{FromPrimitive, IntoPrimitive};
 
 pub type MovePkd = u8;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct MoveUnpkd {
     pub face: Face,
     pub type_: MoveType,
@@ -62,36 +64,64 @@ impl From<&MoveUnpkd> for String {
     }
 }
 
-impl From<&str> for MoveUnpkd {
-    fn from(value: &str) -> Self {
-        let chars: Vec<char> = value.chars().collect();
-        if chars.is_empty() {
-            panic!("invalid string to be converted into MoveUnpkd")
-        }
+/// Returned when a string is not a move in the `<face>[w][' or 2]` notation, e.g. `Rw'` or `U2`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseMoveError {
+    pub token: String,
+}
+
+impl std::fmt::Display for ParseMoveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid move: {:?}", self.token)
+    }
+}
+
+impl std::error::Error for ParseMoveError {}
+
+impl FromStr for MoveUnpkd {
+    type Err = ParseMoveError;
 
-        let face = match chars[0] {
-            'U' => Face::U,
-            'L' => Face::L,
-            'F' => Face::F,
-            'R' => Face::R,
-            'B' => Face::B,
-            'D' => Face::D,
-            _ => panic!("invalid string to be converted into MoveUnpkd"),
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let err = || ParseMoveError {
+            token: value.to_string(),
         };
+        let mut chars = value.chars().peekable();
 
-        let type_ = if chars.len() >= 2 && chars[1] == 'w' {
+        let face = match chars.next() {
+            Some('U') => Face::U,
+            Some('L') => Face::L,
+            Some('F') => Face::F,
+            Some('R') => Face::R,
+            Some('B') => Face::B,
+            Some('D') => Face::D,
+            _ => return Err(err()),
+        };
+
+        let type_ = if chars.next_if_eq(&'w').is_some() {
             MoveType::Wide
         } else {
             MoveType::Outer
         };
 
-        let last_char = chars[chars.len() - 1];
-        let dir = match last_char {
-            '\'' => MoveDir::CCW,
-            '2' => MoveDir::Dub,
-            _ => MoveDir::CW,
+        let dir = match chars.next() {
+            None => MoveDir::CW,
+            Some('\'') => MoveDir::CCW,
+            Some('2') => MoveDir::Dub,
+            _ => return Err(err()),
         };
-        MoveUnpkd { face, type_, dir }
+
+        if chars.next().is_some() {
+            return Err(err());
+        }
+        Ok(MoveUnpkd { face, type_, dir })
+    }
+}
+
+impl From<&str> for MoveUnpkd {
+    fn from(value: &str) -> Self {
+        value
+            .parse()
+            .expect("invalid string to be converted into MoveUnpkd")
     }
 }
 
@@ -106,6 +136,25 @@ pub fn convert_string_to_moves(s: &str) -> Vec<MoveUnpkd> {
         .collect()
 }
 
+/// Like `convert_string_to_moves`, but reports the first invalid move instead of panicking.
+pub fn try_convert_string_to_moves(s: &str) -> Result<Vec<MoveUnpkd>, ParseMoveError> {
+    s.split_whitespace().map(MoveUnpkd::from_str).collect()
+}
+
+/// An endless, deterministic stream of pseudo-random moves (from an LCG seeded with `seed`),
+/// so that tests using random moves are reproducible.
+#[cfg(test)]
+pub(crate) fn pseudo_random_moves(seed: u64) -> impl Iterator<Item = MoveUnpkd> {
+    let mut seed = seed;
+    std::iter::repeat_with(move || {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        let v = (seed >> 33) as u8;
+        MoveUnpkd::from((v % 6) | (((v / 6) % 2) << 3) | (((v / 12) % 3) << 4))
+    })
+}
+
 pub fn convert_moves_to_string(moves: &[MoveUnpkd]) -> String {
     moves.iter().map(String::from).join(" ")
 }
@@ -122,6 +171,18 @@ pub enum Face {
     D = 5,
 }
 
+impl Face {
+    /// Identifies the pair of opposite faces this face belongs to (U/D = 0, L/R = 1, F/B = 2).
+    /// Moves on faces of the same axis commute.
+    pub fn axis(&self) -> u8 {
+        match self {
+            Face::U | Face::D => 0,
+            Face::L | Face::R => 1,
+            Face::F | Face::B => 2,
+        }
+    }
+}
+
 #[derive(IntoPrimitive, FromPrimitive, Debug, PartialEq, Eq, Clone, Copy)]
 #[repr(u8)]
 pub enum MoveType {
@@ -138,3 +199,65 @@ pub enum MoveDir {
     CCW = 1,
     Dub = 2,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn all_moves() -> Vec<MoveUnpkd> {
+        let mut moves = Vec::new();
+        for face in 0..6u8 {
+            for type_ in 0..2u8 {
+                for dir in 0..3u8 {
+                    moves.push(MoveUnpkd {
+                        face: Face::from(face),
+                        type_: MoveType::from(type_),
+                        dir: MoveDir::from(dir),
+                    });
+                }
+            }
+        }
+        moves
+    }
+
+    #[test]
+    fn every_move_round_trips_through_display() {
+        let moves = all_moves();
+        assert_eq!(moves.len(), 36);
+        for m in moves {
+            assert_eq!(m.to_string().parse::<MoveUnpkd>(), Ok(m));
+            assert_eq!(MoveUnpkd::from(MovePkd::from(m)), m);
+        }
+    }
+
+    #[test]
+    fn parser_rejects_invalid_moves() {
+        for token in ["R2'", "Rww", "r", "x", ""] {
+            assert_eq!(
+                token.parse::<MoveUnpkd>(),
+                Err(ParseMoveError {
+                    token: token.to_string()
+                }),
+                "{token:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn try_convert_reports_first_invalid_move() {
+        assert_eq!(
+            try_convert_string_to_moves("R  Uw' F2"),
+            Ok(vec![
+                MoveUnpkd::from("R"),
+                MoveUnpkd::from("Uw'"),
+                MoveUnpkd::from("F2")
+            ])
+        );
+        assert_eq!(
+            try_convert_string_to_moves("R x y"),
+            Err(ParseMoveError {
+                token: "x".to_string()
+            })
+        );
+    }
+}
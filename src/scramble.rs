@@ -0,0 +1,370 @@
+use std::str::FromStr;
+
+use crate::{
+    moves::{Face, MoveType, MoveUnpkd, convert_moves_to_string},
+    state::CubeState,
+};
+
+/// What a scramble has to satisfy to be accepted by `validate_scramble`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScrambleRequirements {
+    pub min_len: usize,
+    pub max_len: usize,
+    /// The faces that wide moves may be made on; outer moves are allowed on every face.
+    pub wide_faces: Vec<Face>,
+    /// The most pieces (as counted by `CubeState::solved_piece_counts`) that may still be solved
+    /// after applying the scramble.
+    pub max_solved_pieces: usize,
+}
+
+impl ScrambleRequirements {
+    /// Requirements matching official 5x5 scrambles: 60 random moves of `<face>[w][' or 2]`.
+    ///
+    /// A random 5x5 state has about 10 solved pieces on average, almost all of them centers.
+    /// No 60-move random-move scramble went above 23 in 20k samples,
+    /// and the limit leaves a margin of two above that.
+    pub fn wca_5x5() -> Self {
+        ScrambleRequirements {
+            min_len: 60,
+            max_len: 60,
+            wide_faces: vec![Face::U, Face::L, Face::F, Face::R, Face::B, Face::D],
+            max_solved_pieces: 25,
+        }
+    }
+
+    /// Requirements matching official 4x4 scrambles, which are random-state:
+    /// their length varies, and the only wide moves they use are `Uw`, `Rw` and `Fw`.
+    ///
+    /// A random 4x4 state has about 5 solved pieces on average.
+    /// No 40-move random-move scramble (using only those wide moves) went above 17
+    /// in 20k samples, and the limit leaves a margin of two above that.
+    pub fn wca_4x4() -> Self {
+        ScrambleRequirements {
+            min_len: 35,
+            max_len: 50,
+            wide_faces: vec![Face::U, Face::R, Face::F],
+            max_solved_pieces: 19,
+        }
+    }
+
+    /// The requirements for the cube size of `S`, if there are any.
+    pub fn wca<S: CubeState>() -> Option<Self> {
+        match S::SIZE {
+            4 => Some(Self::wca_4x4()),
+            5 => Some(Self::wca_5x5()),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ScrambleViolation {
+    /// The token at `index` is not part of the allowed notation.
+    InvalidMove {
+        index: usize,
+        token: String,
+    },
+    /// The move at `index` is a wide move on a face not in `ScrambleRequirements::wide_faces`.
+    DisallowedMove {
+        index: usize,
+        token: String,
+    },
+    TooShort {
+        len: usize,
+        min_len: usize,
+    },
+    TooLong {
+        len: usize,
+        max_len: usize,
+    },
+    /// The move at `index` turns the same layers as the move at `previous_index`,
+    /// with only moves on the same axis (which commute with both) in between,
+    /// so the two could have been written as a single move (or cancel out).
+    RedundantMove {
+        previous_index: usize,
+        index: usize,
+        previous: String,
+        current: String,
+    },
+    NotScrambledEnough {
+        solved_pieces: usize,
+        max_solved_pieces: usize,
+    },
+}
+
+impl std::fmt::Display for ScrambleViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScrambleViolation::InvalidMove { index, token } => {
+                write!(f, "move {} ({token:?}) is not valid notation", index + 1)
+            }
+            ScrambleViolation::DisallowedMove { index, token } => {
+                write!(
+                    f,
+                    "move {} ({token}) is not an allowed wide move",
+                    index + 1
+                )
+            }
+            ScrambleViolation::TooShort { len, min_len } => {
+                write!(
+                    f,
+                    "scramble has {len} moves, at least {min_len} are required"
+                )
+            }
+            ScrambleViolation::TooLong { len, max_len } => {
+                write!(f, "scramble has {len} moves, at most {max_len} are allowed")
+            }
+            ScrambleViolation::RedundantMove {
+                previous_index,
+                index,
+                previous,
+                current,
+            } => write!(
+                f,
+                "moves {} and {} ({previous} ... {current}) turn the same layers with only commuting moves in between",
+                previous_index + 1,
+                index + 1
+            ),
+            ScrambleViolation::NotScrambledEnough {
+                solved_pieces,
+                max_solved_pieces,
+            } => write!(
+                f,
+                "{solved_pieces} pieces are still solved after the scramble, at most {max_solved_pieces} are allowed"
+            ),
+        }
+    }
+}
+
+/// Checks `scramble` against `requirements`, returning every violation found (empty if it is valid).
+///
+/// Redundant moves and the resulting state are only checked if every move could be parsed.
+pub fn validate_scramble<S: CubeState>(
+    scramble: &str,
+    requirements: &ScrambleRequirements,
+) -> Vec<ScrambleViolation> {
+    let mut violations = Vec::new();
+
+    let mut moves = Vec::new();
+    for (index, token) in scramble.split_whitespace().enumerate() {
+        match MoveUnpkd::from_str(token) {
+            Ok(m) => {
+                if m.type_ == MoveType::Wide && !requirements.wide_faces.contains(&m.face) {
+                    violations.push(ScrambleViolation::DisallowedMove {
+                        index,
+                        token: token.to_string(),
+                    });
+                }
+                moves.push(m);
+            }
+            Err(e) => violations.push(ScrambleViolation::InvalidMove {
+                index,
+                token: e.token,
+            }),
+        }
+    }
+    let all_moves_valid = moves.len() == scramble.split_whitespace().count();
+
+    let len = scramble.split_whitespace().count();
+    if len < requirements.min_len {
+        violations.push(ScrambleViolation::TooShort {
+            len,
+            min_len: requirements.min_len,
+        });
+    }
+    if len > requirements.max_len {
+        violations.push(ScrambleViolation::TooLong {
+            len,
+            max_len: requirements.max_len,
+        });
+    }
+
+    if all_moves_valid {
+        // indices of the latest run of consecutive moves on a single axis
+        let mut axis_run: Vec<usize> = Vec::new();
+        for (index, current) in moves.iter().enumerate() {
+            if axis_run
+                .last()
+                .is_some_and(|i| moves[*i].face.axis() != current.face.axis())
+            {
+                axis_run.clear();
+            }
+            if let Some(previous_index) = axis_run
+                .iter()
+                .copied()
+                .find(|i| moves[*i].face == current.face && moves[*i].type_ == current.type_)
+            {
+                violations.push(ScrambleViolation::RedundantMove {
+                    previous_index,
+                    index,
+                    previous: convert_moves_to_string(&[moves[previous_index]]),
+                    current: convert_moves_to_string(&[*current]),
+                });
+            }
+            axis_run.push(index);
+        }
+
+        let mut state = S::default();
+        for m in &moves {
+            state.make_move(*m);
+        }
+        let solved_pieces = state.solved_piece_counts().solved();
+        if solved_pieces > requirements.max_solved_pieces {
+            violations.push(ScrambleViolation::NotScrambledEnough {
+                solved_pieces,
+                max_solved_pieces: requirements.max_solved_pieces,
+            });
+        }
+    }
+
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::moves::pseudo_random_moves;
+    use crate::state::{State, state_4x4::State4x4};
+
+    const SAMPLE_SCRAMBLE: &str = "Fw' R' B Dw Uw Rw Lw' R D2 B2 R' D2 R' Fw2 R' Fw Bw2 Uw' Rw D' L' Bw2 F2 Uw2 Bw' Uw' Rw2 L2 Fw' F' R' Fw' R' Fw' B L2 Bw L2 Bw2 F' R2 D Lw2 L Rw2 R Fw Uw2 Lw D Dw L2 Fw' D Fw2 Lw' Bw' D' Bw2 Rw'";
+
+    fn validate_5x5(scramble: &str) -> Vec<ScrambleViolation> {
+        validate_scramble::<State>(scramble, &ScrambleRequirements::wca_5x5())
+    }
+
+    fn validate_4x4(scramble: &str) -> Vec<ScrambleViolation> {
+        validate_scramble::<State4x4>(scramble, &ScrambleRequirements::wca_4x4())
+    }
+
+    fn is_redundant_move(v: &ScrambleViolation) -> bool {
+        matches!(v, ScrambleViolation::RedundantMove { .. })
+    }
+
+    /// Deterministic pseudo-random scramble that never turns the same layers
+    /// twice within a run of moves on one axis, and only makes wide moves on `wide_faces`.
+    fn pseudo_random_scramble(seed: u64, len: usize, wide_faces: &[Face]) -> String {
+        let mut moves: Vec<MoveUnpkd> = Vec::new();
+        let mut axis_run: Vec<MoveUnpkd> = Vec::new();
+        for m in pseudo_random_moves(seed) {
+            if moves.len() == len {
+                break;
+            }
+            if m.type_ == MoveType::Wide && !wide_faces.contains(&m.face) {
+                continue;
+            }
+            if axis_run
+                .last()
+                .is_some_and(|p| p.face.axis() != m.face.axis())
+            {
+                axis_run.clear();
+            }
+            if axis_run
+                .iter()
+                .any(|p| p.face == m.face && p.type_ == m.type_)
+            {
+                continue;
+            }
+            axis_run.push(m);
+            moves.push(m);
+        }
+        convert_moves_to_string(&moves)
+    }
+
+    #[test]
+    fn sample_scramble_is_valid() {
+        assert_eq!(validate_5x5(SAMPLE_SCRAMBLE), vec![]);
+    }
+
+    #[test]
+    fn random_move_scrambles_are_valid() {
+        // reproduces the calibration of `wca_5x5().max_solved_pieces`
+        let wide_faces = ScrambleRequirements::wca_5x5().wide_faces;
+        for seed in 0..1000 {
+            let scramble = pseudo_random_scramble(seed, 60, &wide_faces);
+            assert_eq!(validate_5x5(&scramble), vec![], "{scramble}");
+        }
+    }
+
+    #[test]
+    fn random_move_4x4_scrambles_are_valid() {
+        // reproduces the calibration of `wca_4x4().max_solved_pieces`
+        let wide_faces = ScrambleRequirements::wca_4x4().wide_faces;
+        for seed in 0..1000 {
+            let scramble = pseudo_random_scramble(seed, 40, &wide_faces);
+            assert_eq!(validate_4x4(&scramble), vec![], "{scramble}");
+        }
+    }
+
+    #[test]
+    fn wca_4x4_only_allows_uw_rw_fw() {
+        let scramble = pseudo_random_scramble(0, 39, &[Face::U, Face::R, Face::F]) + " Lw";
+        assert_eq!(
+            validate_4x4(&scramble),
+            vec![ScrambleViolation::DisallowedMove {
+                index: 39,
+                token: "Lw".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn invalid_token_is_reported_with_its_index() {
+        let scramble = SAMPLE_SCRAMBLE.replacen("B ", "x ", 1);
+        assert_eq!(
+            validate_5x5(&scramble),
+            vec![ScrambleViolation::InvalidMove {
+                index: 2,
+                token: "x".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn repeated_layers_are_redundant() {
+        let violations = validate_5x5("R R'");
+        assert!(violations.contains(&ScrambleViolation::RedundantMove {
+            previous_index: 0,
+            index: 1,
+            previous: "R".to_string(),
+            current: "R'".to_string(),
+        }));
+
+        let violations = validate_5x5("Rw Rw2");
+        assert!(violations.contains(&ScrambleViolation::RedundantMove {
+            previous_index: 0,
+            index: 1,
+            previous: "Rw".to_string(),
+            current: "Rw2".to_string(),
+        }));
+    }
+
+    #[test]
+    fn repeated_layers_across_same_axis_moves_are_redundant() {
+        let violations = validate_5x5("R L R'");
+        assert!(violations.contains(&ScrambleViolation::RedundantMove {
+            previous_index: 0,
+            index: 2,
+            previous: "R".to_string(),
+            current: "R'".to_string(),
+        }));
+
+        // an outer and a wide move on the same face turn different layers,
+        // and a move on another axis ends the run
+        assert!(!validate_5x5("R Rw L").iter().any(is_redundant_move));
+        assert!(!validate_5x5("R U R'").iter().any(is_redundant_move));
+    }
+
+    #[test]
+    fn short_scramble_is_too_short_and_not_scrambled_enough() {
+        let violations = validate_5x5("R U F");
+        assert!(violations.contains(&ScrambleViolation::TooShort {
+            len: 3,
+            min_len: 60
+        }));
+        assert!(
+            violations
+                .iter()
+                .any(|v| matches!(v, ScrambleViolation::NotScrambledEnough { .. }))
+        );
+    }
+}
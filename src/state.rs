@@ -1,11 +1,22 @@
+pub mod facelets;
+pub mod metrics;
+pub mod state_4x4;
 pub mod state_to_img;
+pub mod state_to_text;
 
 use crate::{
     letters_arr,
     moves::{Face, MoveDir, MoveType, MoveUnpkd},
+    state::{
+        facelets::Facelets,
+        metrics::{
+            PieceCount, PieceKind, SolvedPieceCounts, count_solved_centers,
+            count_solved_oriented_pieces, count_solved_pieces,
+        },
+    },
     utils::{
         apply_orbit_with_dir_to_array, apply_orbit_with_dir_to_double_packed_u16,
-        apply_orbit_with_dir_to_packed_u16, is_permutation,
+        apply_orbit_with_dir_to_packed_u16, is_odd_permutation, is_permutation,
     },
 };
 
@@ -138,71 +149,17 @@ impl State {
     }
 
     pub fn get_corners_ori(&self) -> [u8; 8] {
-        let mut res = [0; 8];
-
-        for (i, item) in res.iter_mut().enumerate() {
-            *item = ((self.corners_ori >> (2 * i)) & 3) as u8;
-        }
-
-        res
+        unpack_corners_ori(self.corners_ori)
     }
 
+    /// Checks the invariants of each array on its own (valid permutations, orientation sums,
+    /// four centers of each color), without the parity constraints of `check_solvable`.
     pub fn is_self_valid(&self) -> bool {
-        // CORNERS
-        if !is_permutation(&self.corners_perm) {
-            return false;
-        }
-        for x in self.get_corners_ori() {
-            if x >= 3 {
-                return false;
-            }
-        }
-        let corners_ori_sum: usize = self.get_corners_ori().iter().map(|x| *x as usize).sum();
-        if corners_ori_sum % 3 != 0 {
-            return false;
-        }
-
-        // MIDGES
-        if !is_permutation(&self.midges_perm) {
-            return false;
-        }
-        let midges_ori_sum: usize = self.get_midges_ori().iter().map(|x| *x as usize).sum();
-        if midges_ori_sum % 2 != 0 {
-            return false;
-        }
-
-        // WINGS
-        if !is_permutation(&self.wings) {
-            return false;
-        }
-
-        // + CENTERS
-        for x in self.centers_plus {
-            if x >= 6 {
-                return false;
-            }
-        }
-        for num in 0..6 {
-            let n_tiles_of_num = self.centers_plus.iter().filter(|x| **x == num).count();
-            if n_tiles_of_num != 4 {
-                return false;
-            }
-        }
-
-        // X CENTERS
-        for x in self.centers_x {
-            if x >= 6 {
-                return false;
-            }
-        }
-        for num in 0..6 {
-            let n_tiles_of_num = self.centers_x.iter().filter(|x| **x == num).count();
-            if n_tiles_of_num != 4 {
-                return false;
-            }
-        }
-
-        true
+        check_corners(&self.corners_perm, self.get_corners_ori()).is_ok()
+            && check_midges(&self.midges_perm, self.get_midges_ori()).is_ok()
+            && check_wings(&self.wings).is_ok()
+            && check_centers(CenterKind::Plus, &self.centers_plus).is_ok()
+            && check_centers(CenterKind::X, &self.centers_x).is_ok()
     }
 
     pub fn assert_self_is_valid(&self) {
@@ -229,6 +186,134 @@ impl State {
     }
 }
 
+fn unpack_corners_ori(corners_ori: u16) -> [u8; 8] {
+    let mut res = [0; 8];
+
+    for (i, item) in res.iter_mut().enumerate() {
+        *item = ((corners_ori >> (2 * i)) & 3) as u8;
+    }
+
+    res
+}
+
+fn check_corners(corners_perm: &[u8; 8], corners_ori: [u8; 8]) -> Result<(), SolvabilityError> {
+    if !is_permutation(corners_perm) {
+        return Err(SolvabilityError::InvalidCornerPermutation);
+    }
+    if let Some(pos) = corners_ori.iter().position(|x| *x >= 3) {
+        return Err(SolvabilityError::InvalidCornerOrientation { position: pos });
+    }
+    let corners_ori_sum: usize = corners_ori.iter().map(|x| *x as usize).sum();
+    if !corners_ori_sum.is_multiple_of(3) {
+        return Err(SolvabilityError::TwistedCorner);
+    }
+    Ok(())
+}
+
+fn check_midges(midges_perm: &[u8; 12], midges_ori: [u8; 12]) -> Result<(), SolvabilityError> {
+    if !is_permutation(midges_perm) {
+        return Err(SolvabilityError::InvalidMidgePermutation);
+    }
+    let midges_ori_sum: usize = midges_ori.iter().map(|x| *x as usize).sum();
+    if !midges_ori_sum.is_multiple_of(2) {
+        return Err(SolvabilityError::FlippedMidge);
+    }
+    Ok(())
+}
+
+fn check_wings(wings: &[u8; 24]) -> Result<(), SolvabilityError> {
+    if !is_permutation(wings) {
+        return Err(SolvabilityError::InvalidWingPermutation);
+    }
+    Ok(())
+}
+
+fn check_centers(kind: CenterKind, centers: &[u8; 24]) -> Result<(), SolvabilityError> {
+    for color in 0..6 {
+        let count = centers.iter().filter(|x| **x == color).count();
+        if count != 4 {
+            return Err(SolvabilityError::InvalidCenterColors { kind, color, count });
+        }
+    }
+    Ok(())
+}
+
+/// Which of the two center orbits a `SolvabilityError` refers to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CenterKind {
+    X,
+    Plus,
+}
+
+/// The reason a cube state cannot be reached from the solved state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolvabilityError {
+    InvalidCornerPermutation,
+    InvalidCornerOrientation {
+        position: usize,
+    },
+    TwistedCorner,
+    InvalidMidgePermutation,
+    FlippedMidge,
+    InvalidWingPermutation,
+    InvalidCenterColors {
+        kind: CenterKind,
+        color: u8,
+        count: usize,
+    },
+    CornerMidgeParity,
+}
+
+impl std::fmt::Display for SolvabilityError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolvabilityError::InvalidCornerPermutation => {
+                write!(f, "corners are not a permutation of the 8 corner pieces")
+            }
+            SolvabilityError::InvalidCornerOrientation { position } => {
+                write!(
+                    f,
+                    "corner at position {position} has an orientation outside 0..3"
+                )
+            }
+            SolvabilityError::TwistedCorner => {
+                write!(
+                    f,
+                    "corner orientations do not sum to 0 mod 3 (a corner is twisted)"
+                )
+            }
+            SolvabilityError::InvalidMidgePermutation => {
+                write!(f, "midges are not a permutation of the 12 midge pieces")
+            }
+            SolvabilityError::FlippedMidge => {
+                write!(
+                    f,
+                    "midge orientations do not sum to 0 mod 2 (a midge is flipped)"
+                )
+            }
+            SolvabilityError::InvalidWingPermutation => {
+                write!(f, "wings are not a permutation of the 24 wing pieces")
+            }
+            SolvabilityError::InvalidCenterColors { kind, color, count } => {
+                let kind = match kind {
+                    CenterKind::X => "x-centers",
+                    CenterKind::Plus => "+-centers",
+                };
+                write!(
+                    f,
+                    "{kind} contain {count} tiles of color {color} (expected 4 of each of 0..6)"
+                )
+            }
+            SolvabilityError::CornerMidgeParity => write!(
+                f,
+                "corner and midge permutations have different parities (two corners or two midges are swapped)"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SolvabilityError {}
+
 impl Default for State {
     fn default() -> Self {
         Self::new()
@@ -241,69 +326,241 @@ pub trait MoveableState {
         T: Into<MoveUnpkd>;
 }
 
+/// The interface shared by the states of the different cube sizes,
+/// so that rendering, metrics and scramble checks don't depend on a particular size.
+///
+/// `Default` must give the solved state.
+pub trait CubeState: MoveableState + Clone + Default {
+    /// The number of layers along each edge of the cube.
+    const SIZE: usize;
+
+    /// Checks whether this state can be reached from the solved state by a sequence of moves,
+    /// reporting the first broken constraint if it can't.
+    fn check_solvable(&self) -> Result<(), SolvabilityError>;
+
+    fn is_solvable(&self) -> bool {
+        self.check_solvable().is_ok()
+    }
+
+    fn to_facelets(&self) -> Facelets;
+
+    /// Counts the solved pieces relative to the solved state in its usual orientation.
+    fn solved_piece_counts(&self) -> SolvedPieceCounts;
+
+    /// Like `solved_piece_counts`, but in whichever whole-cube orientation has the most
+    /// solved pieces, so that a rotated cube isn't counted as unsolved.
+    ///
+    /// Cubes with fixed centers can't be rotated by moves, so by default this is the same.
+    fn solved_piece_counts_up_to_rotation(&self) -> SolvedPieceCounts {
+        self.solved_piece_counts()
+    }
+}
+
+impl CubeState for State {
+    const SIZE: usize = 5;
+
+    /// Checks whether this state can be reached from the solved state by a sequence of moves.
+    ///
+    /// On top of the per-array invariants checked by `is_self_valid`, every quarter turn
+    /// (outer or wide) applies a single 4-cycle to both the corners and the midges,
+    /// so their permutation parities must agree.
+    ///
+    /// Wing parity is changed by every wide quarter turn independently of the corners,
+    /// and centers of the same color are indistinguishable (swapping two of them changes
+    /// the parity without changing the state), so neither adds any further constraint.
+    fn check_solvable(&self) -> Result<(), SolvabilityError> {
+        check_corners(&self.corners_perm, self.get_corners_ori())?;
+        check_midges(&self.midges_perm, self.get_midges_ori())?;
+        check_wings(&self.wings)?;
+        check_centers(CenterKind::Plus, &self.centers_plus)?;
+        check_centers(CenterKind::X, &self.centers_x)?;
+
+        // PARITY
+        if is_odd_permutation(&self.corners_perm) != is_odd_permutation(&self.midges_perm) {
+            return Err(SolvabilityError::CornerMidgeParity);
+        }
+
+        Ok(())
+    }
+
+    fn to_facelets(&self) -> Facelets {
+        Facelets::from(self)
+    }
+
+    fn solved_piece_counts(&self) -> SolvedPieceCounts {
+        SolvedPieceCounts(vec![
+            PieceCount {
+                kind: PieceKind::Corner,
+                solved: count_solved_oriented_pieces(&self.corners_perm, &self.get_corners_ori()),
+                total: 8,
+            },
+            PieceCount {
+                kind: PieceKind::Midge,
+                solved: count_solved_oriented_pieces(&self.midges_perm, &self.get_midges_ori()),
+                total: 12,
+            },
+            PieceCount {
+                kind: PieceKind::Wing,
+                solved: count_solved_pieces(&self.wings),
+                total: 24,
+            },
+            PieceCount {
+                kind: PieceKind::XCenter,
+                solved: count_solved_centers(&self.centers_x),
+                total: 24,
+            },
+            PieceCount {
+                kind: PieceKind::PlusCenter,
+                solved: count_solved_centers(&self.centers_plus),
+                total: 24,
+            },
+        ])
+    }
+}
+
 impl MoveableState for State {
     fn make_move<T>(&mut self, m: T)
     where
         T: Into<MoveUnpkd>,
     {
         let m: MoveUnpkd = m.into();
-        let face = u8::from(m.face);
-
-        // CORNERS:
-        let (cp, co) = (&mut self.corners_perm, &mut self.corners_ori);
-        let c_orbit = CORNER_ORBITS[face as usize];
-        let co_changes = CORNER_ORIENTATION_CHANGES[face as usize];
-        apply_orbit_with_dir_to_array(cp, c_orbit, m.dir);
-        apply_orbit_with_dir_to_double_packed_u16(co, c_orbit, m.dir);
-        if m.dir != MoveDir::Dub {
-            for i in 0..4 {
-                let co_old = ((*co >> (2 * c_orbit[i])) & 3) as u8;
-                let co_new = ((co_old + co_changes[i]) % 3) as u16;
-
-                *co &= !(3 << (2 * c_orbit[i]));
-                *co |= co_new << (2 * c_orbit[i]);
-            }
-        }
+        move_corners(&mut self.corners_perm, &mut self.corners_ori, m);
+        move_midges(&mut self.midges_perm, &mut self.midges_ori, m);
+        move_wings(&mut self.wings, m);
+        move_centers_plus(&mut self.centers_plus, m);
+        move_centers_x(&mut self.centers_x, m);
+    }
+}
 
-        // MIDGES
-        let (mp, mo) = (&mut self.midges_perm, &mut self.midges_ori);
-        let m_orbit: [usize; 4] = MIDGE_ORBITS[face as usize];
-        apply_orbit_with_dir_to_array(mp, m_orbit, m.dir);
-        apply_orbit_with_dir_to_packed_u16(mo, m_orbit, m.dir);
-        if m.dir != MoveDir::Dub && (m.face == Face::F || m.face == Face::B) {
-            for orbit_position in m_orbit {
-                *mo ^= 1 << orbit_position;
-            }
-        }
+fn move_corners(cp: &mut [u8; 8], co: &mut u16, m: MoveUnpkd) {
+    let face = u8::from(m.face);
+    let c_orbit = CORNER_ORBITS[face as usize];
+    let co_changes = CORNER_ORIENTATION_CHANGES[face as usize];
+    apply_orbit_with_dir_to_array(cp, c_orbit, m.dir);
+    apply_orbit_with_dir_to_double_packed_u16(co, c_orbit, m.dir);
+    if m.dir != MoveDir::Dub {
+        for i in 0..4 {
+            let co_old = ((*co >> (2 * c_orbit[i])) & 3) as u8;
+            let co_new = ((co_old + co_changes[i]) % 3) as u16;
 
-        // WINGS
-        let w = &mut self.wings;
-        let (w_outer_orbit_1, w_outer_orbit_2) = WING_ORBITS_OUTER[face as usize];
-        let w_wide_orbit = WING_ORBITS_WIDE[face as usize];
-        apply_orbit_with_dir_to_array(w, w_outer_orbit_1, m.dir);
-        apply_orbit_with_dir_to_array(w, w_outer_orbit_2, m.dir);
-        if m.type_ == MoveType::Wide {
-            apply_orbit_with_dir_to_array(w, w_wide_orbit, m.dir);
+            *co &= !(3 << (2 * c_orbit[i]));
+            *co |= co_new << (2 * c_orbit[i]);
         }
+    }
+}
 
-        // PLUS CENTERS
-        let centers_plus = &mut self.centers_plus;
-        // here we exploit a coincidence that the wing orbits happen to be the same as the + center orbits (in speffz)
-        let center_plus_orbit_outer = w_outer_orbit_1;
-        let center_plus_orbit_wide = w_outer_orbit_2;
-        apply_orbit_with_dir_to_array(centers_plus, center_plus_orbit_outer, m.dir);
-        if m.type_ == MoveType::Wide {
-            apply_orbit_with_dir_to_array(centers_plus, center_plus_orbit_wide, m.dir);
+fn move_midges(mp: &mut [u8; 12], mo: &mut u16, m: MoveUnpkd) {
+    let face = u8::from(m.face);
+    let m_orbit: [usize; 4] = MIDGE_ORBITS[face as usize];
+    apply_orbit_with_dir_to_array(mp, m_orbit, m.dir);
+    apply_orbit_with_dir_to_packed_u16(mo, m_orbit, m.dir);
+    if m.dir != MoveDir::Dub && (m.face == Face::F || m.face == Face::B) {
+        for orbit_position in m_orbit {
+            *mo ^= 1 << orbit_position;
         }
+    }
+}
+
+fn move_wings(w: &mut [u8; 24], m: MoveUnpkd) {
+    let face = u8::from(m.face);
+    let (w_outer_orbit_1, w_outer_orbit_2) = WING_ORBITS_OUTER[face as usize];
+    let w_wide_orbit = WING_ORBITS_WIDE[face as usize];
+    apply_orbit_with_dir_to_array(w, w_outer_orbit_1, m.dir);
+    apply_orbit_with_dir_to_array(w, w_outer_orbit_2, m.dir);
+    if m.type_ == MoveType::Wide {
+        apply_orbit_with_dir_to_array(w, w_wide_orbit, m.dir);
+    }
+}
+
+fn move_centers_plus(centers_plus: &mut [u8; 24], m: MoveUnpkd) {
+    let face = u8::from(m.face);
+    // here we exploit a coincidence that the wing orbits happen to be the same as the + center orbits (in speffz)
+    let (center_plus_orbit_outer, center_plus_orbit_wide) = WING_ORBITS_OUTER[face as usize];
+    apply_orbit_with_dir_to_array(centers_plus, center_plus_orbit_outer, m.dir);
+    if m.type_ == MoveType::Wide {
+        apply_orbit_with_dir_to_array(centers_plus, center_plus_orbit_wide, m.dir);
+    }
+}
+
+fn move_centers_x(centers_x: &mut [u8; 24], m: MoveUnpkd) {
+    let face = u8::from(m.face);
+    let (center_x_orbit_outer, _) = WING_ORBITS_OUTER[face as usize];
+    let (center_x_orbit_wide_1, center_x_orbit_wide_2) = CENTER_ORBITS_WIDE_X[face as usize];
+    apply_orbit_with_dir_to_array(centers_x, center_x_orbit_outer, m.dir);
+    if m.type_ == MoveType::Wide {
+        apply_orbit_with_dir_to_array(centers_x, center_x_orbit_wide_1, m.dir);
+        apply_orbit_with_dir_to_array(centers_x, center_x_orbit_wide_2, m.dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::moves::pseudo_random_moves;
 
-        // X CENTERS
-        let centers_x = &mut self.centers_x;
-        let center_x_orbit_outer = w_outer_orbit_1;
-        let (center_x_orbit_wide_1, center_x_orbit_wide_2) = CENTER_ORBITS_WIDE_X[face as usize];
-        apply_orbit_with_dir_to_array(centers_x, center_x_orbit_outer, m.dir);
-        if m.type_ == MoveType::Wide {
-            apply_orbit_with_dir_to_array(centers_x, center_x_orbit_wide_1, m.dir);
-            apply_orbit_with_dir_to_array(centers_x, center_x_orbit_wide_2, m.dir);
+    #[test]
+    fn random_move_sequences_stay_solvable() {
+        for seed in 0..200 {
+            let mut s = State::new();
+            for m in pseudo_random_moves(seed).take(60) {
+                s.make_move(m);
+                assert_eq!(s.check_solvable(), Ok(()));
+            }
         }
     }
+
+    #[test]
+    fn lone_corner_swap_is_unsolvable() {
+        let mut s = State::new();
+        s.corners_perm.swap(0, 1);
+        assert_eq!(s.check_solvable(), Err(SolvabilityError::CornerMidgeParity));
+    }
+
+    #[test]
+    fn lone_midge_swap_is_unsolvable() {
+        let mut s = State::new();
+        s.midges_perm.swap(0, 1);
+        assert_eq!(s.check_solvable(), Err(SolvabilityError::CornerMidgeParity));
+    }
+
+    #[test]
+    fn corner_and_midge_swap_together_is_solvable() {
+        let mut s = State::new();
+        s.corners_perm.swap(0, 1);
+        s.midges_perm.swap(0, 1);
+        assert_eq!(s.check_solvable(), Ok(()));
+    }
+
+    #[test]
+    fn lone_wing_swap_is_solvable() {
+        let mut s = State::new();
+        s.wings.swap(0, 1);
+        assert_eq!(s.check_solvable(), Ok(()));
+    }
+
+    #[test]
+    fn swapped_x_centers_of_different_colors_are_solvable() {
+        let mut s = State::new();
+        s.centers_x.swap(0, 4);
+        assert_ne!(s.centers_x[0], s.centers_x[4]);
+        assert_eq!(s.check_solvable(), Ok(()));
+    }
+
+    #[test]
+    fn self_validity_ignores_parity_only() {
+        let mut s = State::new();
+        assert!(s.is_self_valid());
+        s.corners_perm.swap(0, 1);
+        assert!(s.is_self_valid());
+        assert!(!s.is_solvable());
+        s.midges_ori = 1;
+        assert!(!s.is_self_valid());
+    }
+
+    #[test]
+    fn twisted_corner_is_unsolvable() {
+        let mut s = State::new();
+        s.corners_ori = 1;
+        assert_eq!(s.check_solvable(), Err(SolvabilityError::TwistedCorner));
+    }
 }
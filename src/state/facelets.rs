@@ -0,0 +1,245 @@
+use crate::state::{State, state_4x4::State4x4};
+
+/// The color of every sticker on the cube, as laid out on the net drawn by `state_to_img`.
+///
+/// Faces are indexed in the usual order (0 = U, 1 = L, 2 = F, 3 = R, 4 = B, 5 = D),
+/// and within a face `(x, y)` counts tiles from the top-left corner of that face on the net.
+/// Colors use the same numbering as the center arrays of `State`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Facelets {
+    size: usize,
+    tiles: Vec<u8>,
+}
+
+impl Facelets {
+    pub fn new(size: usize) -> Self {
+        Facelets {
+            size,
+            tiles: vec![0; 6 * size * size],
+        }
+    }
+
+    /// The number of tiles along each edge of a face.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn get(&self, face: usize, x: usize, y: usize) -> u8 {
+        self.tiles[self.index(face, x, y)]
+    }
+
+    pub fn set(&mut self, face: usize, x: usize, y: usize, color: u8) {
+        let index = self.index(face, x, y);
+        self.tiles[index] = color;
+    }
+
+    /// Removes the middle row and column of every face, turning an odd-sized net into
+    /// the net of the next smaller even-sized cube (e.g. 5x5 into 4x4).
+    fn without_middle_layer(&self) -> Facelets {
+        assert!(self.size % 2 == 1);
+        let middle = self.size / 2;
+        let mut res = Facelets::new(self.size - 1);
+        for face in 0..6 {
+            for y in 0..res.size {
+                for x in 0..res.size {
+                    let old_x = if x < middle { x } else { x + 1 };
+                    let old_y = if y < middle { y } else { y + 1 };
+                    res.set(face, x, y, self.get(face, old_x, old_y));
+                }
+            }
+        }
+        res
+    }
+
+    fn index(&self, face: usize, x: usize, y: usize) -> usize {
+        assert!(face < 6 && x < self.size && y < self.size);
+        (face * self.size + y) * self.size + x
+    }
+}
+
+impl From<&State> for Facelets {
+    fn from(state: &State) -> Self {
+        let mut facelets = Facelets::new(5);
+        set_x_centers(&state.centers_x, &mut facelets);
+        set_plus_centers(&state.centers_plus, &mut facelets);
+        set_fixed_centers(&mut facelets);
+        set_corners(&state.corners_perm, state.get_corners_ori(), &mut facelets);
+        set_midges(&state.midges_perm, state.get_midges_ori(), &mut facelets);
+        set_wings(&state.wings, &mut facelets);
+        facelets
+    }
+}
+
+impl From<&State4x4> for Facelets {
+    fn from(state: &State4x4) -> Self {
+        // the pieces of a 4x4 sit where the corresponding 5x5 pieces do once the middle layers are removed
+        let mut facelets = Facelets::new(5);
+        set_x_centers(&state.centers_x, &mut facelets);
+        set_corners(&state.corners_perm, state.get_corners_ori(), &mut facelets);
+        set_wings(&state.wings, &mut facelets);
+        facelets.without_middle_layer()
+    }
+}
+
+fn get_x_center_position(index: usize) -> (usize, usize, usize) {
+    if index >= 24 {
+        panic!("invalid number");
+    }
+
+    let face_num = index / 4;
+
+    let (x, y) = match index % 4 {
+        0 => (1, 1),
+        1 => (3, 1),
+        2 => (3, 3),
+        3 => (1, 3),
+        _ => unreachable!(),
+    };
+
+    (face_num, x, y)
+}
+fn get_plus_center_position(index: usize) -> (usize, usize, usize) {
+    if index >= 24 {
+        panic!("invalid number");
+    }
+
+    let face_num = index / 4;
+
+    let (x, y) = match index % 4 {
+        0 => (2, 1),
+        1 => (3, 2),
+        2 => (2, 3),
+        3 => (1, 2),
+        _ => unreachable!(),
+    };
+
+    (face_num, x, y)
+}
+
+fn set_x_centers(centers_x: &[u8; 24], facelets: &mut Facelets) {
+    for (index, center) in centers_x.iter().enumerate() {
+        let (face, x, y) = get_x_center_position(index);
+        facelets.set(face, x, y, *center);
+    }
+}
+
+fn set_plus_centers(centers_plus: &[u8; 24], facelets: &mut Facelets) {
+    for (index, center) in centers_plus.iter().enumerate() {
+        let (face, x, y) = get_plus_center_position(index);
+        facelets.set(face, x, y, *center);
+    }
+}
+
+fn set_fixed_centers(facelets: &mut Facelets) {
+    for face in 0..6 {
+        facelets.set(face, 2, 2, face as u8);
+    }
+}
+
+fn set_corners(corners_perm: &[u8; 8], corners_ori: [u8; 8], facelets: &mut Facelets) {
+    let corners_colors = [
+        [0, 1, 4],
+        [0, 4, 3],
+        [0, 3, 2],
+        [0, 2, 1],
+        [5, 1, 2],
+        [5, 2, 3],
+        [5, 3, 4],
+        [5, 4, 1],
+    ];
+    let corner_tile_locations = [
+        [(0, 0, 0), (1, 0, 0), (4, 4, 0)],
+        [(0, 4, 0), (4, 0, 0), (3, 4, 0)],
+        [(0, 4, 4), (3, 0, 0), (2, 4, 0)],
+        [(0, 0, 4), (2, 0, 0), (1, 4, 0)],
+        [(5, 0, 0), (1, 4, 4), (2, 0, 4)],
+        [(5, 4, 0), (2, 4, 4), (3, 0, 4)],
+        [(5, 4, 4), (3, 4, 4), (4, 0, 4)],
+        [(5, 0, 4), (4, 4, 4), (1, 0, 4)],
+    ];
+
+    for (index, (corner_piece, piece_orientation)) in
+        corners_perm.iter().zip(corners_ori).enumerate()
+    {
+        let mut c = corners_colors[*corner_piece as usize];
+
+        if piece_orientation == 1 {
+            (c[0], c[1], c[2]) = (c[1], c[2], c[0])
+        } else if piece_orientation == 2 {
+            (c[0], c[1], c[2]) = (c[2], c[0], c[1])
+        }
+
+        let locs = corner_tile_locations[index];
+        for ((face, x, y), color) in locs.iter().zip(c.iter()) {
+            facelets.set(*face, *x, *y, *color);
+        }
+    }
+}
+
+fn set_midges(midges_perm: &[u8; 12], midges_ori: [u8; 12], facelets: &mut Facelets) {
+    #[rustfmt::skip]
+    let midges_colors = [
+        [0, 4], [0, 3], [0, 2], [0, 1],
+        [2, 3], [2, 1],
+        [4, 1], [4, 3],
+        [5, 2], [5, 3], [5, 4], [5, 1],
+    ];
+    let midge_tile_locations = [
+        [(0, 2, 0), (4, 2, 0)],
+        [(0, 4, 2), (3, 2, 0)],
+        [(0, 2, 4), (2, 2, 0)],
+        [(0, 0, 2), (1, 2, 0)],
+        [(2, 4, 2), (3, 0, 2)],
+        [(2, 0, 2), (1, 4, 2)],
+        [(4, 4, 2), (1, 0, 2)],
+        [(4, 0, 2), (3, 4, 2)],
+        [(5, 2, 0), (2, 2, 4)],
+        [(5, 4, 2), (3, 2, 4)],
+        [(5, 2, 4), (4, 2, 4)],
+        [(5, 0, 2), (1, 2, 4)],
+    ];
+
+    for (index, (midge_piece, piece_orientation)) in midges_perm.iter().zip(midges_ori).enumerate()
+    {
+        let mut c = midges_colors[*midge_piece as usize];
+
+        if piece_orientation == 1 {
+            (c[0], c[1]) = (c[1], c[0])
+        }
+
+        let locs = midge_tile_locations[index];
+        for ((face, x, y), color) in locs.iter().zip(c.iter()) {
+            facelets.set(*face, *x, *y, *color);
+        }
+    }
+}
+
+fn set_wings(wings: &[u8; 24], facelets: &mut Facelets) {
+    #[rustfmt::skip]
+    let wing_colors = [
+        [0, 4], [0, 3], [0, 2], [0, 1],
+        [1, 0], [1, 2], [1, 5], [1, 4],
+        [2, 0], [2, 3], [2, 5], [2, 1],
+        [3, 0], [3, 4], [3, 5], [3, 2],
+        [4, 0], [4, 1], [4, 5], [4, 3],
+        [5, 2], [5, 3], [5, 4], [5, 1],
+    ];
+    #[rustfmt::skip]
+    let wing_tile_locations = [
+        [(0,3,0),(4,1,0)],[(0,4,3),(3,1,0)],[(0,1,4),(2,1,0)],[(0,0,1),(1,1,0)],
+        [(1,3,0),(0,0,3)],[(1,4,3),(2,0,3)],[(1,1,4),(5,0,3)],[(1,0,1),(4,4,1)],
+        [(2,3,0),(0,3,4)],[(2,4,3),(3,0,3)],[(2,1,4),(5,1,0)],[(2,0,1),(1,4,1)],
+        [(3,3,0),(0,4,1)],[(3,4,3),(4,0,3)],[(3,1,4),(5,4,1)],[(3,0,1),(2,4,1)],
+        [(4,3,0),(0,1,0)],[(4,4,3),(1,0,3)],[(4,1,4),(5,3,4)],[(4,0,1),(3,4,1)],
+        [(5,3,0),(2,3,4)],[(5,4,3),(3,3,4)],[(5,1,4),(4,3,4)],[(5,0,1),(1,3,4)],
+    ];
+
+    for (index, wing_piece) in wings.iter().enumerate() {
+        let c = wing_colors[*wing_piece as usize];
+
+        let locs = wing_tile_locations[index];
+        for ((face, x, y), color) in locs.iter().zip(c.iter()) {
+            facelets.set(*face, *x, *y, *color);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PieceKind {
+    Corner,
+    Midge,
+    Wing,
+    XCenter,
+    PlusCenter,
+}
+
+impl PieceKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PieceKind::Corner => "corners",
+            PieceKind::Midge => "midges",
+            PieceKind::Wing => "wings",
+            PieceKind::XCenter => "x_centers",
+            PieceKind::PlusCenter => "plus_centers",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PieceCount {
+    pub kind: PieceKind,
+    pub solved: usize,
+    pub total: usize,
+}
+
+/// How many pieces of each type are in their solved position (and orientation).
+///
+/// A center counts as solved when it has the color of the face it is on,
+/// since centers of the same color are interchangeable.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SolvedPieceCounts(pub Vec<PieceCount>);
+
+impl SolvedPieceCounts {
+    pub fn solved(&self) -> usize {
+        self.0.iter().map(|c| c.solved).sum()
+    }
+
+    pub fn total(&self) -> usize {
+        self.0.iter().map(|c| c.total).sum()
+    }
+}
+
+pub(super) fn count_solved_centers(centers: &[u8; 24]) -> usize {
+    centers
+        .iter()
+        .enumerate()
+        .filter(|(i, color)| (i / 4) as u8 == **color)
+        .count()
+}
+
+/// Counts the pieces that are in their own position with orientation 0.
+pub(super) fn count_solved_oriented_pieces(perm: &[u8], ori: &[u8]) -> usize {
+    perm.iter()
+        .zip(ori)
+        .enumerate()
+        .filter(|(i, (piece, ori))| *i == **piece as usize && **ori == 0)
+        .count()
+}
+
+pub(super) fn count_solved_pieces(perm: &[u8]) -> usize {
+    perm.iter()
+        .enumerate()
+        .filter(|(i, piece)| *i == **piece as usize)
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{CubeState, MoveableState, State};
+
+    #[test]
+    fn solved_state_counts_every_piece() {
+        let counts = State::new().solved_piece_counts();
+        assert_eq!((counts.solved(), counts.total()), (92, 92));
+    }
+
+    #[test]
+    fn counts_after_r() {
+        let mut s = State::new();
+        s.make_move("R");
+        let solved: Vec<usize> = s.solved_piece_counts().0.iter().map(|c| c.solved).collect();
+        // 4 corners, 4 midges and 8 wings move; the R centers move but stay red
+        assert_eq!(solved, [4, 8, 16, 24, 24]);
+    }
+
+    #[test]
+    fn counting_helpers() {
+        assert_eq!(count_solved_pieces(&[0, 2, 1, 3]), 2);
+        assert_eq!(
+            count_solved_oriented_pieces(&[0, 1, 3, 2], &[0, 1, 0, 0]),
+            1
+        );
+
+        let mut centers = [
+            0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5,
+        ];
+        assert_eq!(count_solved_centers(&centers), 24);
+        // swapping two centers of the same color changes nothing
+        centers.swap(0, 1);
+        assert_eq!(count_solved_centers(&centers), 24);
+        centers.swap(0, 4);
+        assert_eq!(count_solved_centers(&centers), 22);
+    }
+}
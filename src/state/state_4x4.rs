@@ -0,0 +1,250 @@
+use crate::{
+    moves::{MoveUnpkd, convert_string_to_moves},
+    state::{
+        CenterKind, CubeState, MoveableState, SolvabilityError, check_centers, check_corners,
+        check_wings,
+        facelets::Facelets,
+        metrics::{
+            PieceCount, PieceKind, SolvedPieceCounts, count_solved_centers,
+            count_solved_oriented_pieces, count_solved_pieces,
+        },
+        move_centers_x, move_corners, move_wings, unpack_corners_ori,
+    },
+};
+
+/// Encodes the state of a 4x4 cube.
+///
+/// A 4x4 is a 5x5 without the midges and the +-centers, and the speffz lettering
+/// of its wings and centers matches the 5x5 wings and x-centers, so all the pieces
+/// are ordered and moved exactly like the corresponding pieces of `State`.
+///
+/// Since a 4x4 has no fixed centers, states that only differ by a rotation
+/// of the whole cube (e.g. after `Rw Lw'`) are considered different.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct State4x4 {
+    /// Same as `State::corners_perm`.
+    pub corners_perm: [u8; 8],
+    /// Same as `State::corners_ori`.
+    pub corners_ori: u16,
+    /// Same as `State::wings`.
+    pub wings: [u8; 24],
+    /// Same as `State::centers_x`.
+    pub centers_x: [u8; 24],
+}
+
+/// Whole-cube rotations that bring each face to the top (`x`, `x2`, `x'`, `z`, `z'`),
+/// written as pairs of opposite wide moves, which together turn every layer of a 4x4.
+const ROTATIONS_TO_TOP: [&str; 6] = ["", "Rw Lw'", "Rw2 Lw2", "Rw' Lw", "Fw Bw'", "Fw' Bw"];
+/// Whole-cube rotations around the U-D axis (`y`, `y2`, `y'`).
+const ROTATIONS_AROUND_UD: [&str; 4] = ["", "Uw Dw'", "Uw2 Dw2", "Uw' Dw"];
+
+impl State4x4 {
+    pub fn get_corners_ori(&self) -> [u8; 8] {
+        unpack_corners_ori(self.corners_ori)
+    }
+
+    pub fn new() -> Self {
+        State4x4 {
+            corners_perm: [0, 1, 2, 3, 4, 5, 6, 7],
+            corners_ori: 0,
+            wings: [
+                0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22,
+                23,
+            ],
+            centers_x: [
+                0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5,
+            ],
+        }
+    }
+}
+
+impl Default for State4x4 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MoveableState for State4x4 {
+    fn make_move<T>(&mut self, m: T)
+    where
+        T: Into<MoveUnpkd>,
+    {
+        let m: MoveUnpkd = m.into();
+        move_corners(&mut self.corners_perm, &mut self.corners_ori, m);
+        move_wings(&mut self.wings, m);
+        move_centers_x(&mut self.centers_x, m);
+    }
+}
+
+impl CubeState for State4x4 {
+    const SIZE: usize = 4;
+
+    /// Checks whether this state can be reached from the solved state by a sequence of moves.
+    ///
+    /// Unlike on the 5x5, there is no parity constraint between orbits:
+    /// wing parity is changed by wide quarter turns independently of the corners,
+    /// and the centers of the same color are indistinguishable.
+    fn check_solvable(&self) -> Result<(), SolvabilityError> {
+        check_corners(&self.corners_perm, self.get_corners_ori())?;
+        check_wings(&self.wings)?;
+        check_centers(CenterKind::X, &self.centers_x)?;
+        Ok(())
+    }
+
+    fn to_facelets(&self) -> Facelets {
+        Facelets::from(self)
+    }
+
+    fn solved_piece_counts_up_to_rotation(&self) -> SolvedPieceCounts {
+        let mut best = self.solved_piece_counts();
+        for to_top in ROTATIONS_TO_TOP {
+            for around_ud in ROTATIONS_AROUND_UD {
+                let mut rotated = self.clone();
+                for m in convert_string_to_moves(to_top)
+                    .into_iter()
+                    .chain(convert_string_to_moves(around_ud))
+                {
+                    rotated.make_move(m);
+                }
+                let counts = rotated.solved_piece_counts();
+                if counts.solved() > best.solved() {
+                    best = counts;
+                }
+            }
+        }
+        best
+    }
+
+    fn solved_piece_counts(&self) -> SolvedPieceCounts {
+        SolvedPieceCounts(vec![
+            PieceCount {
+                kind: PieceKind::Corner,
+                solved: count_solved_oriented_pieces(&self.corners_perm, &self.get_corners_ori()),
+                total: 8,
+            },
+            PieceCount {
+                kind: PieceKind::Wing,
+                solved: count_solved_pieces(&self.wings),
+                total: 24,
+            },
+            PieceCount {
+                kind: PieceKind::XCenter,
+                solved: count_solved_centers(&self.centers_x),
+                total: 24,
+            },
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::moves::pseudo_random_moves;
+
+    fn state_after(moves: &str) -> State4x4 {
+        let mut s = State4x4::new();
+        for m in convert_string_to_moves(moves) {
+            s.make_move(m);
+        }
+        s
+    }
+
+    fn face_rows(facelets: &Facelets, face: usize) -> [[u8; 4]; 4] {
+        let mut rows = [[0; 4]; 4];
+        for (y, row) in rows.iter_mut().enumerate() {
+            for (x, tile) in row.iter_mut().enumerate() {
+                *tile = facelets.get(face, x, y);
+            }
+        }
+        rows
+    }
+
+    #[test]
+    fn every_quarter_move_has_order_four() {
+        for face in ["U", "L", "F", "R", "B", "D"] {
+            for m in [face.to_string(), format!("{face}w")] {
+                let s = state_after(&[m.as_str(); 4].join(" "));
+                assert_eq!(s, State4x4::new(), "{m}");
+            }
+        }
+    }
+
+    #[test]
+    fn random_move_sequences_stay_solvable() {
+        for seed in 0..200 {
+            let mut s = State4x4::new();
+            for m in pseudo_random_moves(seed).take(40) {
+                s.make_move(m);
+                assert_eq!(s.check_solvable(), Ok(()));
+            }
+        }
+    }
+
+    #[test]
+    fn solved_facelets_have_one_color_per_face() {
+        let facelets = State4x4::new().to_facelets();
+        assert_eq!(facelets.size(), 4);
+        for face in 0..6 {
+            assert_eq!(face_rows(&facelets, face), [[face as u8; 4]; 4]);
+        }
+    }
+
+    #[test]
+    fn facelets_after_rw_u() {
+        let facelets = state_after("Rw U").to_facelets();
+
+        // U: the green columns brought up by Rw end up at the bottom after U
+        assert_eq!(
+            face_rows(&facelets, 0),
+            [[0, 0, 0, 0], [0, 0, 0, 0], [2, 2, 2, 2], [2, 2, 2, 2]]
+        );
+        // L
+        assert_eq!(
+            face_rows(&facelets, 1),
+            [[2, 2, 5, 5], [1, 1, 1, 1], [1, 1, 1, 1], [1, 1, 1, 1]]
+        );
+        // F
+        assert_eq!(
+            face_rows(&facelets, 2),
+            [[3, 3, 3, 3], [2, 2, 5, 5], [2, 2, 5, 5], [2, 2, 5, 5]]
+        );
+        // R
+        assert_eq!(
+            face_rows(&facelets, 3),
+            [[0, 0, 4, 4], [3, 3, 3, 3], [3, 3, 3, 3], [3, 3, 3, 3]]
+        );
+        // B
+        assert_eq!(
+            face_rows(&facelets, 4),
+            [[1, 1, 1, 1], [0, 0, 4, 4], [0, 0, 4, 4], [0, 0, 4, 4]]
+        );
+        // D
+        assert_eq!(face_rows(&facelets, 5), [[5, 5, 4, 4]; 4]);
+    }
+
+    #[test]
+    fn solved_piece_counts() {
+        let counts = State4x4::new().solved_piece_counts();
+        assert_eq!((counts.solved(), counts.total()), (56, 56));
+
+        // R moves 4 corners and 8 wings out of place; the R centers move but stay red
+        let counts = state_after("R").solved_piece_counts();
+        assert_eq!(counts.solved(), 56 - 12);
+    }
+
+    #[test]
+    fn rotated_solved_cube_is_solved_up_to_rotation() {
+        let rotated = state_after("Rw Lw'");
+        assert_ne!(rotated.solved_piece_counts().solved(), 56);
+        assert_eq!(rotated.solved_piece_counts_up_to_rotation().solved(), 56);
+
+        // all 24 orientations are reachable and recognised
+        for to_top in ROTATIONS_TO_TOP {
+            for around_ud in ROTATIONS_AROUND_UD {
+                let s = state_after(&format!("R U' Fw {to_top} {around_ud}"));
+                let counts = s.solved_piece_counts_up_to_rotation();
+                assert_eq!(counts, state_after("R U' Fw").solved_piece_counts());
+            }
+        }
+    }
+}
@@ -1,31 +1,41 @@
-use crate::state::State;
+use crate::state::CubeState;
+use crate::state::facelets::Facelets;
 
-use image::{ImageBuffer, Rgb, RgbImage};
+use image::{ImageBuffer, ImageResult, Rgb, RgbImage, imageops};
 use imageproc::drawing::draw_filled_rect_mut;
 use imageproc::rect::Rect;
 
 // Global constants for the frame
 const LINE_WIDTH: u32 = 2;
 const CELL_SIZE: u32 = 20;
-const CUBE_SIZE: u32 = 5; // 5x5 cube
-
-const FACE_SIZE: u32 = CUBE_SIZE * CELL_SIZE + LINE_WIDTH;
+// Space between the renders in a strip
+const STRIP_GAP: u32 = 10;
 
+/// Where each face goes on the net, in units of whole faces.
 const FACES_START_POS: [(u32, u32); 6] = [
     // Top face
-    (FACE_SIZE, 0),
+    (1, 0),
     // Left face
-    (0, FACE_SIZE),
+    (0, 1),
     // Center face
-    (FACE_SIZE, FACE_SIZE),
+    (1, 1),
     // Right face
-    (2 * FACE_SIZE, FACE_SIZE),
+    (2, 1),
     // Back face
-    (3 * FACE_SIZE, FACE_SIZE),
+    (3, 1),
     // Bottom face
-    (FACE_SIZE, 2 * FACE_SIZE),
+    (1, 2),
 ];
 
+fn face_size(cube_size: u32) -> u32 {
+    cube_size * CELL_SIZE + LINE_WIDTH
+}
+
+fn face_start_pos(face: usize, cube_size: u32) -> (u32, u32) {
+    let (col, row) = FACES_START_POS[face];
+    (col * face_size(cube_size), row * face_size(cube_size))
+}
+
 // Colors
 const BLACK: Rgb<u8> = Rgb([0, 0, 0]);
 
@@ -56,8 +66,8 @@ fn draw_vertical_line(img: &mut RgbImage, x: u32, y: u32, length: u32) {
     }
 }
 
-fn draw_face_frame(img: &mut RgbImage, start_x: u32, start_y: u32) {
-    let face_size = CUBE_SIZE * CELL_SIZE;
+fn draw_face_frame(img: &mut RgbImage, start_x: u32, start_y: u32, cube_size: u32) {
+    let face_size = cube_size * CELL_SIZE;
 
     // Draw outer border
     draw_horizontal_line(img, start_x, start_y, face_size + LINE_WIDTH);
@@ -66,7 +76,7 @@ fn draw_face_frame(img: &mut RgbImage, start_x: u32, start_y: u32) {
     draw_vertical_line(img, start_x + face_size, start_y, face_size + LINE_WIDTH);
 
     // Draw internal grid lines
-    for i in 1..CUBE_SIZE {
+    for i in 1..cube_size {
         // Horizontal internal lines
         draw_horizontal_line(
             img,
@@ -85,19 +95,19 @@ fn draw_face_frame(img: &mut RgbImage, start_x: u32, start_y: u32) {
 }
 
 // This function (and its callees) were pretty much completely generated by Claude.
-fn draw_rubiks_cube_frame() -> RgbImage {
+fn draw_rubiks_cube_frame(cube_size: u32) -> RgbImage {
     // Calculate total image dimensions for the cross layout
     // Width: 4 faces wide (left face + center column + right face + extra space)
     // Height: 3 faces tall (top face + center row + bottom face)
-    let img_width = 4 * FACE_SIZE;
-    let img_height = 3 * FACE_SIZE;
+    let img_width = 4 * face_size(cube_size);
+    let img_height = 3 * face_size(cube_size);
 
     let mut img: RgbImage = ImageBuffer::from_pixel(img_width, img_height, Rgb([125, 125, 125]));
 
     // Draw the 6 faces of the cube net:
-    for face_start_pos in FACES_START_POS {
-        let (x, y) = face_start_pos;
-        draw_face_frame(&mut img, x, y);
+    for face in 0..6 {
+        let (x, y) = face_start_pos(face, cube_size);
+        draw_face_frame(&mut img, x, y, cube_size);
     }
 
     img
@@ -115,194 +125,80 @@ fn num_to_color(num: u8) -> Rgb<u8> {
     }
 }
 
-fn get_x_center_draw_position(index: usize) -> (usize, usize, usize) {
-    if index >= 24 {
-        panic!("invalid number");
-    }
-
-    let face_num = index / 4;
-
-    let (x, y) = match index % 4 {
-        0 => (1, 1),
-        1 => (3, 1),
-        2 => (3, 3),
-        3 => (1, 3),
-        _ => unreachable!(),
-    };
-
-    (face_num, x, y)
-}
-fn get_plus_center_draw_position(index: usize) -> (usize, usize, usize) {
-    if index >= 24 {
-        panic!("invalid number");
-    }
-
-    let face_num = index / 4;
-
-    let (x, y) = match index % 4 {
-        0 => (2, 1),
-        1 => (3, 2),
-        2 => (2, 3),
-        3 => (1, 2),
-        _ => unreachable!(),
-    };
-
-    (face_num, x, y)
-}
-
-fn draw_tile(img: &mut RgbImage, face_x_y: (usize, usize, usize), color: u8) {
+fn draw_tile(img: &mut RgbImage, face_x_y: (usize, usize, usize), color: u8, cube_size: u32) {
     let color = num_to_color(color);
-    let (x, y) = get_location(face_x_y.0, face_x_y.1, face_x_y.2);
+    let (x, y) = get_location(face_x_y.0, face_x_y.1, face_x_y.2, cube_size);
     let rect = Rect::at(x as i32, y as i32).of_size(CELL_SIZE - LINE_WIDTH, CELL_SIZE - LINE_WIDTH);
     draw_filled_rect_mut(img, rect, color);
 }
 
-fn get_location(face: usize, x_tiles: usize, y_tiles: usize) -> (u32, u32) {
-    let x = FACES_START_POS[face].0 + CELL_SIZE * x_tiles as u32 + LINE_WIDTH;
-    let y = FACES_START_POS[face].1 + CELL_SIZE * y_tiles as u32 + LINE_WIDTH;
+fn get_location(face: usize, x_tiles: usize, y_tiles: usize, cube_size: u32) -> (u32, u32) {
+    let (face_x, face_y) = face_start_pos(face, cube_size);
+    let x = face_x + CELL_SIZE * x_tiles as u32 + LINE_WIDTH;
+    let y = face_y + CELL_SIZE * y_tiles as u32 + LINE_WIDTH;
     (x, y)
 }
 
-fn draw_centers_on_image(state: &State, img: &mut RgbImage) {
-    for (index, center) in state.centers_x.iter().enumerate() {
-        let (face, x_tiles, y_tiles) = get_x_center_draw_position(index);
-        draw_tile(img, (face, x_tiles, y_tiles), *center);
-    }
-
-    for (index, center) in state.centers_plus.iter().enumerate() {
-        let (face, x_tiles, y_tiles) = get_plus_center_draw_position(index);
-        draw_tile(img, (face, x_tiles, y_tiles), *center);
-    }
-
+fn draw_facelets_on_image(facelets: &Facelets, img: &mut RgbImage) {
+    let cube_size = facelets.size() as u32;
     for face in 0..6 {
-        draw_tile(img, (face, 2, 2), face as u8);
-    }
-}
-
-fn draw_corners_on_image(state: &State, img: &mut RgbImage) {
-    let corners_colors = [
-        [0, 1, 4],
-        [0, 4, 3],
-        [0, 3, 2],
-        [0, 2, 1],
-        [5, 1, 2],
-        [5, 2, 3],
-        [5, 3, 4],
-        [5, 4, 1],
-    ];
-    let corner_tile_locations = [
-        [(0, 0, 0), (1, 0, 0), (4, 4, 0)],
-        [(0, 4, 0), (4, 0, 0), (3, 4, 0)],
-        [(0, 4, 4), (3, 0, 0), (2, 4, 0)],
-        [(0, 0, 4), (2, 0, 0), (1, 4, 0)],
-        [(5, 0, 0), (1, 4, 4), (2, 0, 4)],
-        [(5, 4, 0), (2, 4, 4), (3, 0, 4)],
-        [(5, 4, 4), (3, 4, 4), (4, 0, 4)],
-        [(5, 0, 4), (4, 4, 4), (1, 0, 4)],
-    ];
-
-    for (index, (corner_piece, piece_orientation)) in state
-        .corners_perm
-        .iter()
-        .zip(state.get_corners_ori())
-        .enumerate()
-    {
-        let mut c = corners_colors[*corner_piece as usize];
-
-        if piece_orientation == 1 {
-            (c[0], c[1], c[2]) = (c[1], c[2], c[0])
-        } else if piece_orientation == 2 {
-            (c[0], c[1], c[2]) = (c[2], c[0], c[1])
-        }
-
-        let locs = corner_tile_locations[index];
-        for (loc, color) in locs.iter().zip(c.iter()) {
-            draw_tile(img, *loc, *color);
-        }
-    }
-}
-
-fn draw_midges_on_image(state: &State, img: &mut RgbImage) {
-    #[rustfmt::skip]
-    let midges_colors = [
-        [0, 4], [0, 3], [0, 2], [0, 1],
-        [2, 3], [2, 1],
-        [4, 1], [4, 3],
-        [5, 2], [5, 3], [5, 4], [5, 1],
-    ];
-    let midge_tile_locations = [
-        [(0, 2, 0), (4, 2, 0)],
-        [(0, 4, 2), (3, 2, 0)],
-        [(0, 2, 4), (2, 2, 0)],
-        [(0, 0, 2), (1, 2, 0)],
-        [(2, 4, 2), (3, 0, 2)],
-        [(2, 0, 2), (1, 4, 2)],
-        [(4, 4, 2), (1, 0, 2)],
-        [(4, 0, 2), (3, 4, 2)],
-        [(5, 2, 0), (2, 2, 4)],
-        [(5, 4, 2), (3, 2, 4)],
-        [(5, 2, 4), (4, 2, 4)],
-        [(5, 0, 2), (1, 2, 4)],
-    ];
-
-    for (index, (midge_piece, piece_orientation)) in state
-        .midges_perm
-        .iter()
-        .zip(state.get_midges_ori())
-        .enumerate()
-    {
-        let mut c = midges_colors[*midge_piece as usize];
-
-        if piece_orientation == 1 {
-            (c[0], c[1]) = (c[1], c[0])
-        }
-
-        let locs = midge_tile_locations[index];
-        for (loc, color) in locs.iter().zip(c.iter()) {
-            draw_tile(img, *loc, *color);
+        for y in 0..facelets.size() {
+            for x in 0..facelets.size() {
+                draw_tile(img, (face, x, y), facelets.get(face, x, y), cube_size);
+            }
         }
     }
 }
 
-fn draw_wings_on_image(state: &State, img: &mut RgbImage) {
-    #[rustfmt::skip]
-    let wing_colors = [
-        [0, 4], [0, 3], [0, 2], [0, 1],
-        [1, 0], [1, 2], [1, 5], [1, 4],
-        [2, 0], [2, 3], [2, 5], [2, 1],
-        [3, 0], [3, 4], [3, 5], [3, 2],
-        [4, 0], [4, 1], [4, 5], [4, 3],
-        [5, 2], [5, 3], [5, 4], [5, 1],
-    ];
-    #[rustfmt::skip]
-    let wing_tile_locations = [
-        [(0,3,0),(4,1,0)],[(0,4,3),(3,1,0)],[(0,1,4),(2,1,0)],[(0,0,1),(1,1,0)],
-        [(1,3,0),(0,0,3)],[(1,4,3),(2,0,3)],[(1,1,4),(5,0,3)],[(1,0,1),(4,4,1)],
-        [(2,3,0),(0,3,4)],[(2,4,3),(3,0,3)],[(2,1,4),(5,1,0)],[(2,0,1),(1,4,1)],
-        [(3,3,0),(0,4,1)],[(3,4,3),(4,0,3)],[(3,1,4),(5,4,1)],[(3,0,1),(2,4,1)],
-        [(4,3,0),(0,1,0)],[(4,4,3),(1,0,3)],[(4,1,4),(5,3,4)],[(4,0,1),(3,4,1)],
-        [(5,3,0),(2,3,4)],[(5,4,3),(3,3,4)],[(5,1,4),(4,3,4)],[(5,0,1),(1,3,4)],
-    ];
-
-    for (index, wing_piece) in state.wings.iter().enumerate() {
-        let c = wing_colors[*wing_piece as usize];
-
-        let locs = wing_tile_locations[index];
-        for (loc, color) in locs.iter().zip(c.iter()) {
-            draw_tile(img, *loc, *color);
-        }
-    }
+pub fn state_to_image<S: CubeState>(state: &S) -> RgbImage {
+    let mut img = draw_rubiks_cube_frame(S::SIZE as u32);
+    draw_facelets_on_image(&state.to_facelets(), &mut img);
+    img
 }
 
-pub fn export_state_to_image(state: &State, file_path: &str) {
-    let mut img = draw_rubiks_cube_frame();
-    draw_centers_on_image(state, &mut img);
-    draw_corners_on_image(state, &mut img);
-    draw_midges_on_image(state, &mut img);
-    draw_wings_on_image(state, &mut img);
-    img.save(file_path).expect("Failed to save state image");
+pub fn export_state_to_image<S: CubeState>(state: &S, file_path: &str) -> ImageResult<()> {
+    let img = state_to_image(state);
+    img.save(file_path)?;
 
     // println!("Rubik's cube frame saved as '{}'", file_path);
     // println!("Image dimensions: {}x{}", img.width(), img.height());
     // println!("Cell size: {}, Line width: {}", CELL_SIZE, LINE_WIDTH);
+    Ok(())
+}
+
+/// Saves half-size renders of `states` side by side, starting a new row every `columns` states.
+pub fn export_states_strip_to_image<S: CubeState>(
+    states: &[S],
+    columns: usize,
+    file_path: &str,
+) -> ImageResult<()> {
+    assert!(!states.is_empty() && columns > 0);
+    let minis: Vec<RgbImage> = states
+        .iter()
+        .map(|state| {
+            let img = state_to_image(state);
+            imageops::resize(
+                &img,
+                img.width() / 2,
+                img.height() / 2,
+                imageops::FilterType::Triangle,
+            )
+        })
+        .collect();
+
+    let (mini_width, mini_height) = minis[0].dimensions();
+    let (cell_width, cell_height) = (mini_width + STRIP_GAP, mini_height + STRIP_GAP);
+    let columns = columns.min(minis.len());
+    let rows = minis.len().div_ceil(columns);
+    let mut strip: RgbImage = ImageBuffer::from_pixel(
+        columns as u32 * cell_width - STRIP_GAP,
+        rows as u32 * cell_height - STRIP_GAP,
+        Rgb([255, 255, 255]),
+    );
+    for (i, mini) in minis.iter().enumerate() {
+        let x = (i % columns) as u32 * cell_width;
+        let y = (i / columns) as u32 * cell_height;
+        imageops::replace(&mut strip, mini, x as i64, y as i64);
+    }
+    strip.save(file_path)
 }
@@ -0,0 +1,71 @@
+use crate::state::CubeState;
+use crate::state::facelets::Facelets;
+
+/// Where each face goes on the net, in units of whole faces (same layout as `state_to_img`).
+const FACES_START_POS: [(usize, usize); 6] = [
+    // Top face
+    (1, 0),
+    // Left face
+    (0, 1),
+    // Center face
+    (1, 1),
+    // Right face
+    (2, 1),
+    // Back face
+    (3, 1),
+    // Bottom face
+    (1, 2),
+];
+
+const RESET: &str = "\x1b[0m";
+
+/// Each tile is drawn as two spaces with a 256-color background,
+/// so that the tiles come out roughly square in most terminals.
+fn num_to_ansi_background(num: u8) -> &'static str {
+    match num {
+        0 => "\x1b[48;5;231m", // white
+        1 => "\x1b[48;5;208m", // orange
+        2 => "\x1b[48;5;28m",  // green
+        3 => "\x1b[48;5;160m", // red
+        4 => "\x1b[48;5;26m",  // blue
+        5 => "\x1b[48;5;220m", // yellow
+        _ => panic!("invalid number"),
+    }
+}
+
+pub fn facelets_to_ansi_string(facelets: &Facelets) -> String {
+    let size = facelets.size();
+    // one character column of padding between neighbouring faces
+    let face_width = 2 * size + 1;
+
+    let mut rows: Vec<String> = Vec::with_capacity(3 * size);
+    for row in 0..3 * size {
+        let mut line = String::new();
+        let mut line_width = 0;
+        for (face, (face_col, face_row)) in FACES_START_POS.iter().enumerate() {
+            if row / size != *face_row {
+                continue;
+            }
+            // faces in a row are visited left to right, so only padding is needed in between
+            let start = face_col * face_width;
+            line.push_str(&" ".repeat(start - line_width));
+            for x in 0..size {
+                line.push_str(num_to_ansi_background(facelets.get(face, x, row % size)));
+                line.push_str("  ");
+            }
+            line.push_str(RESET);
+            line_width = start + 2 * size;
+        }
+        rows.push(line);
+        // blank line between rows of faces
+        if row % size == size - 1 && row != 3 * size - 1 {
+            rows.push(String::new());
+        }
+    }
+
+    rows.join("\n")
+}
+
+pub fn state_to_ansi_string<S: CubeState>(state: &S) -> String {
+    facelets_to_ansi_string(&state.to_facelets())
+}
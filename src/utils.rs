@@ -28,6 +28,42 @@ where
     true
 }
 
+/// Returns whether `slice`, which must be a permutation of `0..slice.len()`,
+/// is made up of an odd number of transpositions.
+///
+/// # Panics
+///
+/// May panic (or, in release builds, return a meaningless result)
+/// if `slice` is not a permutation; check it with `is_permutation` first.
+pub fn is_odd_permutation<T>(slice: &[T]) -> bool
+where
+    T: Copy,
+    usize: From<T>,
+{
+    debug_assert!(is_permutation(slice));
+    let n = slice.len();
+    let mut visited = vec![false; n];
+    let mut n_even_length_cycles = 0;
+
+    for start in 0..n {
+        if visited[start] {
+            continue;
+        }
+        let mut cycle_len = 0;
+        let mut idx = start;
+        while !visited[idx] {
+            visited[idx] = true;
+            idx = usize::from(slice[idx]);
+            cycle_len += 1;
+        }
+        if cycle_len % 2 == 0 {
+            n_even_length_cycles += 1;
+        }
+    }
+
+    n_even_length_cycles % 2 == 1
+}
+
 pub fn apply_orbit_with_dir_to_array<T>(arr: &mut [T], orbit: [usize; 4], dir: MoveDir)
 where
     T: Copy,
@@ -153,3 +189,20 @@ macro_rules! letters_arr {
         RESULT
     }};
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn odd_permutation_parity() {
+        assert!(!is_odd_permutation::<u8>(&[]));
+        assert!(!is_odd_permutation::<u8>(&[0, 1, 2, 3]));
+        assert!(is_odd_permutation::<u8>(&[1, 0, 2, 3]));
+        // a 3-cycle is even, a 4-cycle is odd
+        assert!(!is_odd_permutation::<u8>(&[1, 2, 0, 3]));
+        assert!(is_odd_permutation::<u8>(&[1, 2, 3, 0]));
+        // two disjoint transpositions are even
+        assert!(!is_odd_permutation::<u8>(&[1, 0, 3, 2]));
+    }
+}